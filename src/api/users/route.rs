@@ -1,18 +1,64 @@
 use axum::Extension;
 use axum::Router;
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::extract::{Path, Query, State};
+use axum::middleware;
 use axum::response::Json;
 use axum::routing::{delete, get, patch, post};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
 use crate::app::AppState;
-use crate::auth::UserContext;
-use crate::db::models::users::{NewUser, User, UserRecord, UserUpdate, UserUpdateRecord};
+use crate::auth::{
+    PERMISSION_DELETE, PERMISSION_READ, PERMISSION_WRITE, UserContext, require_permission,
+};
+use crate::db::models::users::{
+    DUMMY_PASSWORD_HASH, NewUser, User, UserRecord, UserState, UserUpdate, UserUpdateRecord,
+    verify_password,
+};
 use crate::db::record::IntoNewRecord;
-use crate::error::{bad_request_error, database_error, internal_error};
+use crate::error::{AppError, ErrorResponse};
+use crate::pagination::{Page, decode_cursor, encode_cursor};
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Query parameters accepted by `list_users`.
+#[derive(Deserialize, IntoParams)]
+pub struct ListUsersParams {
+    /// Maximum number of users to return (default 50, capped at 200).
+    pub limit: Option<i64>,
+    /// Opaque cursor from a previous page's `next_cursor`.
+    pub cursor: Option<String>,
+    /// Only return users whose username contains this substring.
+    pub username: Option<String>,
+    /// When true, also include soft-deleted users. Intended for admin callers.
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+/// Query parameters accepted by `get_user`.
+#[derive(Deserialize, IntoParams)]
+pub struct GetUserParams {
+    /// When true, also return the user if it has been soft-deleted.
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+/// Credentials accepted by `POST /users/login`.
+#[derive(Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Response body for a successful login.
+#[derive(Serialize, ToSchema)]
+pub struct LoginResponse {
+    pub user: User,
+}
 
 /// ==============================================
 ///        INDEX :: `/api/users/route.rs`
@@ -23,49 +69,171 @@ use crate::error::{bad_request_error, database_error, internal_error};
 ///   get_user()     ::     GET   /users/{id}
 ///   update_user()  ::   PATCH   /users/{id}
 ///   delete_user()  ::  DELETE   /users/{id}
+///   restore_user() ::    POST   /users/{id}/restore
+///   login()        ::    POST   /users/login
 ///
 /// ==============================================
 
+/// Unauthenticated routes, mounted outside the `inject_user_context`
+/// middleware so a caller can check credentials without a bearer token.
+pub fn public_router() -> Router<AppState> {
+    Router::new().route("/login", post(login))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/", get(list_users))
-        .route("/", post(create_user))
-        .route("/{id}", get(get_user))
-        .route("/{id}", patch(update_user))
-        .route("/{id}", delete(delete_user))
+        .route(
+            "/",
+            get(list_users).route_layer(middleware::from_fn(require_permission(
+                "read",
+                PERMISSION_READ,
+            ))),
+        )
+        .route(
+            "/",
+            post(create_user).route_layer(middleware::from_fn(require_permission(
+                "write",
+                PERMISSION_WRITE,
+            ))),
+        )
+        .route(
+            "/{id}",
+            get(get_user).route_layer(middleware::from_fn(require_permission(
+                "read",
+                PERMISSION_READ,
+            ))),
+        )
+        .route(
+            "/{id}",
+            patch(update_user).route_layer(middleware::from_fn(require_permission(
+                "write",
+                PERMISSION_WRITE,
+            ))),
+        )
+        .route(
+            "/{id}",
+            delete(delete_user).route_layer(middleware::from_fn(require_permission(
+                "users:delete",
+                PERMISSION_DELETE,
+            ))),
+        )
+        .route(
+            "/{id}/restore",
+            post(restore_user).route_layer(middleware::from_fn(require_permission(
+                "users:delete",
+                PERMISSION_DELETE,
+            ))),
+        )
 }
 
 /// - GET /users -
 ///
-/// Returns a list of all users.
+/// Returns a page of users, ordered by `(created_at, id)`, optionally
+/// filtered by a `username` substring.
+#[utoipa::path(
+    get,
+    path = "/users",
+    params(ListUsersParams),
+    responses(
+        (status = 200, description = "A page of users", body = Page<User>),
+        (status = 400, description = "Invalid pagination cursor", body = ErrorResponse),
+        (status = 500, description = "Internal error", body = ErrorResponse),
+    ),
+)]
 pub async fn list_users(
     State(app_state): State<AppState>,
-    Extension(_current_user): Extension<UserContext>,
-) -> Result<Json<Vec<User>>, (StatusCode, String)> {
+    Extension(current_user): Extension<UserContext>,
+    Query(params): Query<ListUsersParams>,
+) -> Result<Json<Page<User>>, AppError> {
     use crate::schema::users::dsl::*;
 
-    let mut conn = app_state.db_pool.get().await.map_err(internal_error)?;
-    let records: Vec<UserRecord> = users.load(&mut conn).await.map_err(database_error)?;
+    if params.include_deleted && !current_user.has_permission("users:delete", PERMISSION_DELETE) {
+        return Err(AppError::Forbidden(
+            "include_deleted requires the users:delete permission".to_string(),
+        ));
+    }
+
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    let mut conn = app_state
+        .db_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut query = users.into_boxed();
+
+    if !params.include_deleted {
+        query = query.filter(state.ne(UserState::Deleted));
+    }
+
+    if let Some(name_filter) = params.username {
+        query = query.filter(username.like(format!("%{name_filter}%")));
+    }
+
+    if let Some(cursor) = params.cursor {
+        let (last_created_at, last_id) = decode_cursor(&cursor)?;
+        query = query.filter(
+            created_at
+                .gt(last_created_at)
+                .or(created_at.eq(last_created_at).and(id.gt(last_id))),
+        );
+    }
+
+    let records: Vec<UserRecord> = query
+        .order((created_at.asc(), id.asc()))
+        .limit(limit)
+        .load(&mut conn)
+        .await?;
+
+    let next_cursor = (records.len() as i64 == limit)
+        .then(|| records.last().map(|r| encode_cursor(r.created_at, &r.id)))
+        .flatten();
+
     let data: Vec<User> = records.into_iter().map(|record| record.into()).collect();
-    Ok(Json(data))
+    Ok(Json(Page { data, next_cursor }))
 }
 
 /// GET /users/{id}
 ///
 /// Returns a single user by ID.
+#[utoipa::path(
+    get,
+    path = "/users/{id}",
+    params(("id" = String, Path, description = "The user's Clerk ID"), GetUserParams),
+    responses(
+        (status = 200, description = "The requested user", body = User),
+        (status = 404, description = "No user with that ID", body = ErrorResponse),
+    ),
+)]
 pub async fn get_user(
     State(app_state): State<AppState>,
-    Extension(_current_user): Extension<UserContext>,
+    Extension(current_user): Extension<UserContext>,
     Path(id): Path<String>,
-) -> Result<Json<User>, (StatusCode, String)> {
-    use crate::schema::users::dsl::{id as user_id, users};
+    Query(params): Query<GetUserParams>,
+) -> Result<Json<User>, AppError> {
+    use crate::schema::users::dsl::{id as user_id, state, users};
 
-    let mut conn = app_state.db_pool.get().await.map_err(internal_error)?;
-    let record: UserRecord = users
-        .filter(user_id.eq(id))
-        .first(&mut conn)
+    if params.include_deleted && !current_user.has_permission("users:delete", PERMISSION_DELETE) {
+        return Err(AppError::Forbidden(
+            "include_deleted requires the users:delete permission".to_string(),
+        ));
+    }
+
+    let mut conn = app_state
+        .db_pool
+        .get()
         .await
-        .map_err(database_error)?;
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut query = users.filter(user_id.eq(id)).into_boxed();
+    if !params.include_deleted {
+        query = query.filter(state.ne(UserState::Deleted));
+    }
+    let record: UserRecord = query.first(&mut conn).await?;
 
     let user: User = record.into();
     Ok(Json(user))
@@ -74,23 +242,41 @@ pub async fn get_user(
 /// POST /users
 ///
 /// Handles the creation of a new user.
+#[utoipa::path(
+    post,
+    path = "/users",
+    request_body = NewUser,
+    responses(
+        (status = 200, description = "The newly created user", body = User),
+        (status = 400, description = "Validation failed", body = ErrorResponse),
+        (status = 409, description = "Username already taken", body = ErrorResponse),
+    ),
+)]
 pub async fn create_user(
     State(app_state): State<AppState>,
     Extension(_current_user): Extension<UserContext>,
     Json(new_user): Json<NewUser>,
-) -> Result<Json<User>, (StatusCode, String)> {
+) -> Result<Json<User>, AppError> {
     use crate::schema::users::dsl::*;
 
-    new_user.validate().map_err(bad_request_error)?;
+    new_user.validate()?;
 
-    let mut conn = app_state.db_pool.get().await.map_err(internal_error)?;
+    let mut conn = app_state
+        .db_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let record: UserRecord = new_user.into_new_record();
+    // `into_new_record` hashes the password with Argon2, which is
+    // CPU-bound, so build the record on the blocking thread pool rather
+    // than stalling the async runtime.
+    let record: UserRecord = tokio::task::spawn_blocking(move || new_user.into_new_record())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
     let record = diesel::insert_into(users)
         .values(record)
         .get_result::<UserRecord>(&mut conn)
-        .await
-        .map_err(database_error)?;
+        .await?;
 
     let user: User = record.into();
     Ok(Json(user))
@@ -99,24 +285,38 @@ pub async fn create_user(
 /// PATCH /users/{id}
 ///
 /// Updates an existing user.
+#[utoipa::path(
+    patch,
+    path = "/users/{id}",
+    params(("id" = String, Path, description = "The user's Clerk ID")),
+    request_body = UserUpdate,
+    responses(
+        (status = 200, description = "The updated user", body = User),
+        (status = 400, description = "Validation failed", body = ErrorResponse),
+        (status = 404, description = "No user with that ID", body = ErrorResponse),
+    ),
+)]
 pub async fn update_user(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
     Json(update): Json<UserUpdate>,
-) -> Result<Json<User>, (StatusCode, String)> {
+) -> Result<Json<User>, AppError> {
     use crate::schema::users::dsl::{id as user_id, users};
 
-    update.validate().map_err(bad_request_error)?;
+    update.validate()?;
     let update: UserUpdateRecord = update.into();
 
-    let mut conn = app_state.db_pool.get().await.map_err(internal_error)?;
+    let mut conn = app_state
+        .db_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
     let record = diesel::update(users)
         .filter(user_id.eq(id))
         .set(&update)
         .returning(users::all_columns())
         .get_result::<UserRecord>(&mut conn)
-        .await
-        .map_err(database_error)?;
+        .await?;
 
     let user: User = record.into();
     Ok(Json(user))
@@ -125,27 +325,126 @@ pub async fn update_user(
 /// DELETE /users/{id}
 ///
 /// Soft deletes a user by setting its state to Deleted and returns the updated user data.
+#[utoipa::path(
+    delete,
+    path = "/users/{id}",
+    params(("id" = String, Path, description = "The user's Clerk ID")),
+    responses(
+        (status = 200, description = "The deleted user", body = User),
+        (status = 404, description = "No user with that ID", body = ErrorResponse),
+    ),
+)]
 pub async fn delete_user(
     State(app_state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<User>, (StatusCode, String)> {
-    use crate::schema::users::dsl::{id as user_id, users};
-
-    let mut conn = app_state.db_pool.get().await.map_err(internal_error)?;
+) -> Result<Json<User>, AppError> {
+    use crate::schema::users::dsl::{deleted_at, id as user_id, state, users};
 
-    // Get the user record before deleting it so we can return it
-    let record = users
-        .filter(user_id.eq(&id))
-        .first::<UserRecord>(&mut conn)
+    let mut conn = app_state
+        .db_pool
+        .get()
         .await
-        .map_err(database_error)?;
+        .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Perform the actual deletion
-    diesel::delete(users.filter(user_id.eq(id)))
-        .execute(&mut conn)
+    let now = jiff::Timestamp::now().as_millisecond();
+    let record = diesel::update(users)
+        .filter(user_id.eq(id).and(state.ne(UserState::Deleted)))
+        .set((state.eq(UserState::Deleted), deleted_at.eq(Some(now))))
+        .returning(users::all_columns())
+        .get_result::<UserRecord>(&mut conn)
+        .await?;
+
+    let user: User = record.into();
+    Ok(Json(user))
+}
+
+/// POST /users/{id}/restore
+///
+/// Restores a previously soft-deleted user, flipping its state back to
+/// `Active` and clearing `deleted_at`.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/restore",
+    params(("id" = String, Path, description = "The user's Clerk ID")),
+    responses(
+        (status = 200, description = "The restored user", body = User),
+        (status = 404, description = "No soft-deleted user with that ID", body = ErrorResponse),
+    ),
+)]
+pub async fn restore_user(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<User>, AppError> {
+    use crate::schema::users::dsl::{deleted_at, id as user_id, state, users};
+
+    let mut conn = app_state
+        .db_pool
+        .get()
         .await
-        .map_err(database_error)?;
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let record = diesel::update(users)
+        .filter(user_id.eq(id).and(state.eq(UserState::Deleted)))
+        .set((state.eq(UserState::Active), deleted_at.eq(None::<i64>)))
+        .returning(users::all_columns())
+        .get_result::<UserRecord>(&mut conn)
+        .await?;
 
     let user: User = record.into();
     Ok(Json(user))
 }
+
+/// POST /users/login
+///
+/// Verifies a username/password pair against the stored Argon2 hash.
+#[utoipa::path(
+    post,
+    path = "/users/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Successful login", body = LoginResponse),
+        (status = 401, description = "Invalid username or password", body = ErrorResponse),
+    ),
+)]
+pub async fn login(
+    State(app_state): State<AppState>,
+    Json(credentials): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    use crate::schema::users::dsl::{username as username_col, users};
+
+    let mut conn = app_state
+        .db_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let record: Option<UserRecord> = users
+        .filter(username_col.eq(&credentials.username))
+        .first(&mut conn)
+        .await
+        .optional()?;
+
+    // Always run the Argon2 check, even when the username doesn't exist,
+    // falling back to a dummy hash so a missing user takes the same time
+    // as a wrong password instead of leaking username validity via timing.
+    let password_hash = record
+        .as_ref()
+        .and_then(|r| r.password_hash.clone())
+        .unwrap_or_else(|| DUMMY_PASSWORD_HASH.to_string());
+    let password = credentials.password.clone();
+
+    // Argon2 hashing is CPU-bound, so verification runs on the blocking
+    // thread pool rather than stalling the async runtime.
+    let verified = tokio::task::spawn_blocking(move || verify_password(&password_hash, &password))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(record) = record.filter(|_| verified) else {
+        return Err(AppError::Unauthorized(
+            "Invalid username or password".to_string(),
+        ));
+    };
+
+    let user: User = record.into();
+    Ok(Json(LoginResponse { user }))
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use axum::Router;
 use axum::middleware;
 use axum::routing::get;
@@ -5,14 +7,21 @@ use diesel_async::AsyncPgConnection;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use hyper::StatusCode;
 use tower_http::trace::{DefaultMakeSpan, TraceLayer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 // - crate -
 use crate::api::users::route as users;
-use crate::auth::inject_user_context;
+use crate::auth::{JwksCache, inject_user_context};
+use crate::openapi::ApiDoc;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db_pool: deadpool::managed::Pool<AsyncDieselConnectionManager<AsyncPgConnection>>,
+    /// Cache of the Clerk instance's JWKS signing keys, keyed by `kid`.
+    pub jwks_cache: Arc<JwksCache>,
+    /// The `azp` (authorized party) every session token must have been issued for.
+    pub authorized_party: String,
 }
 
 pub fn build_public_router() -> Router {
@@ -20,16 +29,36 @@ pub fn build_public_router() -> Router {
 }
 
 pub fn build_api_router(app_state: AppState) -> Router {
-    Router::new()
+    let protected = Router::new()
         .nest("/users", users::router())
         .with_state(app_state.clone())
-        .layer(middleware::from_fn(inject_user_context))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            inject_user_context,
+        ));
+
+    let public = Router::new()
+        .nest("/users", users::public_router())
+        .with_state(app_state);
+
+    Router::new()
+        .merge(protected)
+        .merge(public)
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::default().include_headers(true)),
         )
 }
 
-async fn health_check() -> (StatusCode, &'static str) {
+/// GET /health
+///
+/// Liveness check used by load balancers and orchestrators.
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "The service is up", body = String)),
+)]
+pub(crate) async fn health_check() -> (StatusCode, &'static str) {
     (StatusCode::OK, "OK")
 }
@@ -1,8 +1,20 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use axum::Extension;
 use axum::body::Body;
-use axum::http::Request;
+use axum::extract::State;
+use axum::http::{HeaderMap, Request};
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::app::AppState;
+use crate::error::AppError;
 
 /// Organization claims extracted from a Clerk v2 session token.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -19,6 +31,24 @@ pub struct OrgContext {
     pub feature_permission_map: Vec<i64>,
 }
 
+impl From<ClerkOrgClaim> for OrgContext {
+    fn from(claim: ClerkOrgClaim) -> Self {
+        let permissions = split_claim_list(&claim.permissions);
+        let feature_permission_map = split_claim_list(&claim.feature_permission_map)
+            .into_iter()
+            .filter_map(|bit| bit.parse().ok())
+            .collect();
+
+        OrgContext {
+            id: claim.id,
+            slug: claim.slug,
+            role: claim.role,
+            permissions,
+            feature_permission_map,
+        }
+    }
+}
+
 /// Data made available to handlers once a caller is authenticated.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UserContext {
@@ -30,19 +60,271 @@ pub struct UserContext {
     pub org: Option<OrgContext>,
 }
 
-pub async fn inject_user_context(mut req: Request<Body>, next: Next) -> Response {
-    let user_context = UserContext {
-        id: "user_dummy".to_string(),
-        roles: vec!["dummy_role".to_string()],
-        org: Some(OrgContext {
-            id: "org_dummy".to_string(),
-            slug: "dummy-org".to_string(),
-            role: "dummy_admin".to_string(),
-            permissions: vec!["read".to_string(), "write".to_string()],
-            feature_permission_map: vec![1, 2],
-        }),
-    };
-    req.extensions_mut().insert(user_context);
-
-    next.run(req).await
+/// Bit layout of `OrgContext.feature_permission_map` entries.
+pub const PERMISSION_READ: i64 = 1;
+pub const PERMISSION_WRITE: i64 = 2;
+pub const PERMISSION_DELETE: i64 = 4;
+
+impl UserContext {
+    /// Returns whether this user's org grants `feature` with `action_bit`
+    /// set in its corresponding `feature_permission_map` entry.
+    pub fn has_permission(&self, feature: &str, action_bit: i64) -> bool {
+        let Some(org) = &self.org else {
+            return false;
+        };
+
+        org.permissions
+            .iter()
+            .position(|permission| permission == feature)
+            .and_then(|idx| org.feature_permission_map.get(idx))
+            .is_some_and(|bits| bits & action_bit == action_bit)
+    }
+}
+
+/// Clerk v2 session token claims relevant to this service.
+///
+/// Clerk packs organization data under the short `o` claim with
+/// abbreviated field names to keep the token small; `permissions` and
+/// `feature_permission_map` are comma-separated and parallel each other.
+#[derive(Debug, Deserialize)]
+struct ClerkClaims {
+    /// Subject: the Clerk user ID.
+    sub: String,
+    /// Authorized party: the client ID the token was issued to.
+    azp: Option<String>,
+    /// Custom claim carrying the user's global roles.
+    #[serde(default)]
+    roles: Vec<String>,
+    /// Organization claim, present only when the session is org-scoped.
+    #[serde(default)]
+    o: Option<ClerkOrgClaim>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClerkOrgClaim {
+    id: String,
+    #[serde(rename = "slg")]
+    slug: String,
+    #[serde(rename = "rol")]
+    role: String,
+    #[serde(rename = "per", default)]
+    permissions: String,
+    #[serde(rename = "fpm", default)]
+    feature_permission_map: String,
+}
+
+fn split_claim_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Errors that can occur while authenticating an inbound request.
+#[derive(Debug)]
+pub enum AuthError {
+    /// No `Authorization: Bearer` header was present.
+    MissingToken,
+    /// The token's header was missing a `kid` or otherwise malformed.
+    MalformedToken,
+    /// The token's `kid` was not found in the JWKS, even after a refresh.
+    UnknownKid,
+    /// Fetching or parsing the issuer's JWKS document failed.
+    JwksUnavailable(String),
+    /// Signature verification or claim validation failed.
+    InvalidToken(jsonwebtoken::errors::Error),
+    /// The token's `azp` claim did not match this service's authorized party.
+    UnauthorizedParty,
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "Missing Authorization bearer token"),
+            AuthError::MalformedToken => write!(f, "Malformed bearer token"),
+            AuthError::UnknownKid => write!(f, "Unknown JWKS key id"),
+            AuthError::JwksUnavailable(msg) => write!(f, "Could not refresh JWKS: {msg}"),
+            AuthError::InvalidToken(err) => write!(f, "Invalid token: {err}"),
+            AuthError::UnauthorizedParty => write!(f, "Token was not issued for this client"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Minimum time between JWKS refreshes triggered by an unrecognized `kid`.
+/// Without this, a client can mint a fresh bogus `kid` per request and force
+/// an outbound fetch against the issuer on every single request.
+const JWKS_REFRESH_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// A cache of JWKS signing keys for a Clerk instance, keyed by `kid`.
+///
+/// Keys are fetched lazily on first use and re-fetched on a cache miss,
+/// so rotating the issuer's signing key does not require a restart.
+pub struct JwksCache {
+    issuer: String,
+    http_client: reqwest::Client,
+    keys: RwLock<HashMap<String, DecodingKey>>,
+    last_refreshed: RwLock<Option<Instant>>,
+}
+
+impl JwksCache {
+    pub fn new(issuer: impl Into<String>) -> Self {
+        JwksCache {
+            issuer: issuer.into(),
+            http_client: reqwest::Client::new(),
+            keys: RwLock::new(HashMap::new()),
+            last_refreshed: RwLock::new(None),
+        }
+    }
+
+    /// Returns the decoding key for `kid`, refreshing the JWKS once if it
+    /// is not already cached. Refreshes are throttled by
+    /// [`JWKS_REFRESH_COOLDOWN`] so a stream of requests with bogus `kid`s
+    /// can't force a fetch against the issuer on every request.
+    async fn decoding_key(&self, kid: &str) -> Result<DecodingKey, AuthError> {
+        if let Some(key) = self.keys.read().await.get(kid) {
+            return Ok(key.clone());
+        }
+
+        if !self.should_refresh().await {
+            return Err(AuthError::UnknownKid);
+        }
+
+        self.refresh().await?;
+
+        self.keys
+            .read()
+            .await
+            .get(kid)
+            .cloned()
+            .ok_or(AuthError::UnknownKid)
+    }
+
+    async fn should_refresh(&self) -> bool {
+        match *self.last_refreshed.read().await {
+            Some(last) => last.elapsed() >= JWKS_REFRESH_COOLDOWN,
+            None => true,
+        }
+    }
+
+    async fn refresh(&self) -> Result<(), AuthError> {
+        *self.last_refreshed.write().await = Some(Instant::now());
+
+        let url = format!("{}/.well-known/jwks.json", self.issuer);
+
+        let jwks: JwksDocument = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|err| AuthError::JwksUnavailable(err.to_string()))?
+            .json()
+            .await
+            .map_err(|err| AuthError::JwksUnavailable(err.to_string()))?;
+
+        let mut keys = self.keys.write().await;
+        keys.clear();
+        for jwk in jwks.keys {
+            match DecodingKey::from_rsa_components(&jwk.n, &jwk.e) {
+                Ok(key) => {
+                    keys.insert(jwk.kid, key);
+                }
+                Err(err) => {
+                    tracing::warn!(kid = %jwk.kid, error = %err, "Skipping unparseable JWKS entry");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JwksDocument {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Jwk {
+    kid: String,
+    n: String,
+    e: String,
+}
+
+/// Axum middleware that authenticates the request's bearer token against
+/// Clerk and inserts the resulting [`UserContext`] as a request extension.
+pub async fn inject_user_context(
+    State(app_state): State<AppState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    match authenticate(&app_state, req.headers()).await {
+        Ok(user_context) => {
+            req.extensions_mut().insert(user_context);
+            next.run(req).await
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "Request authentication failed");
+            AppError::Unauthorized(err.to_string()).into_response()
+        }
+    }
+}
+
+async fn authenticate(
+    app_state: &AppState,
+    headers: &HeaderMap,
+) -> Result<UserContext, AuthError> {
+    let token = bearer_token(headers)?;
+
+    let header = jsonwebtoken::decode_header(token).map_err(|_| AuthError::MalformedToken)?;
+    let kid = header.kid.ok_or(AuthError::MalformedToken)?;
+    let decoding_key = app_state.jwks_cache.decoding_key(&kid).await?;
+
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_issuer(&[app_state.jwks_cache.issuer.as_str()]);
+    validation.validate_nbf = true;
+
+    let data = jsonwebtoken::decode::<ClerkClaims>(token, &decoding_key, &validation)
+        .map_err(AuthError::InvalidToken)?;
+    let claims = data.claims;
+
+    if claims.azp.as_deref() != Some(app_state.authorized_party.as_str()) {
+        return Err(AuthError::UnauthorizedParty);
+    }
+
+    Ok(UserContext {
+        id: claims.sub,
+        roles: claims.roles,
+        org: claims.o.map(OrgContext::from),
+    })
+}
+
+fn bearer_token(headers: &HeaderMap) -> Result<&str, AuthError> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(AuthError::MissingToken)
+}
+
+/// Builds a `tower`/`axum` middleware that rejects the request with `403`
+/// unless the caller's `UserContext` (inserted by [`inject_user_context`])
+/// has `action_bit` set for `feature`. Apply with
+/// `.route_layer(middleware::from_fn(require_permission("users:delete", PERMISSION_DELETE)))`.
+pub fn require_permission(
+    feature: &'static str,
+    action_bit: i64,
+) -> impl Fn(Extension<UserContext>, Request<Body>, Next) -> Pin<Box<dyn Future<Output = Response> + Send>>
++ Clone {
+    move |Extension(user_context): Extension<UserContext>, req: Request<Body>, next: Next| {
+        Box::pin(async move {
+            if !user_context.has_permission(feature, action_bit) {
+                return AppError::Forbidden(format!("Missing permission: {feature}")).into_response();
+            }
+
+            next.run(req).await
+        })
+    }
 }
@@ -0,0 +1,3 @@
+pub mod migrations;
+pub mod models;
+pub mod record;
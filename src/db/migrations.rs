@@ -0,0 +1,42 @@
+use diesel::Connection;
+use diesel::pg::PgConnection;
+use diesel_async::AsyncPgConnection;
+use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Applies all pending migrations to the database backing `pool`.
+///
+/// `diesel_migrations` runs synchronously, while this service's pool is
+/// `diesel_async`, so the migration step is performed on a dedicated sync
+/// connection established directly from `DATABASE_URL`, inside a blocking
+/// task.
+pub async fn run_pending_migrations(
+    pool: &Pool<AsyncDieselConnectionManager<AsyncPgConnection>>,
+) -> Result<(), String> {
+    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set".to_string())?;
+
+    // Touch the pool so a misconfigured pool fails fast.
+    pool.get()
+        .await
+        .map_err(|err| format!("Failed to acquire a pooled connection: {err}"))?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut conn = PgConnection::establish(&database_url)
+            .map_err(|err| format!("Failed to connect for migrations: {err}"))?;
+
+        let applied = conn
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|err| format!("Failed to run migrations: {err}"))?;
+
+        for migration in applied {
+            tracing::info!("Applied migration: {migration}");
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(|err| format!("Migration task panicked: {err}"))?
+}
@@ -1,11 +1,90 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
 use jiff::Timestamp;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use validator::Validate;
 
 use crate::db::record::IntoNewRecord;
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+/// Lifecycle state of a user. Stored as a `TEXT` column so soft-deleted
+/// rows can be filtered with a plain `state != 'deleted'` predicate.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum UserState {
+    Active,
+    Deleted,
+}
+
+impl UserState {
+    fn as_str(self) -> &'static str {
+        match self {
+            UserState::Active => "active",
+            UserState::Deleted => "deleted",
+        }
+    }
+}
+
+impl Default for UserState {
+    fn default() -> Self {
+        UserState::Active
+    }
+}
+
+impl ToSql<Text, Pg> for UserState {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), &mut out.reborrow())
+    }
+}
+
+impl FromSql<Text, Pg> for UserState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Pg>>::from_sql(bytes)?.as_str() {
+            "active" => Ok(UserState::Active),
+            "deleted" => Ok(UserState::Deleted),
+            other => Err(format!("Unrecognized user state: {other}").into()),
+        }
+    }
+}
+
+/// Hashes `password` with Argon2id using a random salt, returning the PHC
+/// string to persist in `password_hash`.
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Argon2 hashing failed")
+        .to_string()
+}
+
+/// Verifies `candidate` against a PHC-formatted `hash` in constant time.
+pub fn verify_password(hash: &str, candidate: &str) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(candidate.as_bytes(), &parsed_hash)
+        .is_ok()
+}
+
+/// A PHC hash of no real password. Callers that can't find a user record
+/// should still verify against this so that a missing username takes the
+/// same time as a wrong password, instead of leaking username validity
+/// through response timing.
+pub const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$c21rYmR1bW15c2FsdHZhbHVl$3W1lZHVtbXlkdW1teWhhc2h2YWx1ZTEyMzQ1Njc4OTA";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, ToSchema)]
 pub struct User {
     /// The Clerk ID of the user, used as the primary identifier for this user entity.
     pub id: String,
@@ -17,13 +96,17 @@ pub struct User {
     /// An optional last name for the user.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_name: Option<String>,
+    /// The user's lifecycle state.
+    pub state: UserState,
     /// Unix timestamp marking the creation of the user account.
+    #[schema(value_type = String)]
     pub created_at: Timestamp,
     /// Unix timestamp marking the last update to the user account.
+    #[schema(value_type = String)]
     pub updated_at: Timestamp,
 }
 
-#[derive(Serialize, Deserialize, Validate, Clone, Default)]
+#[derive(Serialize, Deserialize, Validate, Clone, Default, ToSchema)]
 pub struct NewUser {
     /// The username to give to the user. It must be unique across your instance.
     pub username: String,
@@ -37,11 +120,12 @@ pub struct NewUser {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
     /// Timestamp of when the user was created
+    #[schema(value_type = Option<String>)]
     pub created_at: Option<Timestamp>,
 }
 
 /// User update input type
-#[derive(Serialize, Deserialize, Validate, Clone, Default)]
+#[derive(Serialize, Deserialize, Validate, Clone, Default, ToSchema)]
 pub struct UserUpdate {
     /// The username to give to the user. It must be unique across your instance.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -52,6 +136,7 @@ pub struct UserUpdate {
         skip_serializing_if = "Option::is_none",
         with = "::serde_with::rust::double_option"
     )]
+    #[schema(value_type = Option<String>)]
     pub first_name: Option<Option<String>>,
     /// The last name to assign to the user
     #[serde(
@@ -59,6 +144,7 @@ pub struct UserUpdate {
         skip_serializing_if = "Option::is_none",
         with = "::serde_with::rust::double_option"
     )]
+    #[schema(value_type = Option<String>)]
     pub last_name: Option<Option<String>>,
 }
 
@@ -70,6 +156,9 @@ pub struct UserRecord {
     pub username: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
+    pub password_hash: Option<String>,
+    pub state: UserState,
+    pub deleted_at: Option<i64>,
     pub updated_at: i64,
     pub created_at: i64,
 }
@@ -97,6 +186,7 @@ impl From<UserRecord> for User {
             username: record.username,
             first_name: record.first_name,
             last_name: record.last_name,
+            state: record.state,
             updated_at,
             created_at,
         }
@@ -114,6 +204,9 @@ impl IntoNewRecord for NewUser {
             username: self.username,
             first_name: self.first_name,
             last_name: self.last_name,
+            password_hash: self.password.as_deref().map(hash_password),
+            state: UserState::Active,
+            deleted_at: None,
 
             created_at: current_time.as_millisecond(),
             updated_at: current_time.as_millisecond(),
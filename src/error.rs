@@ -1,101 +1,78 @@
+use axum::Json;
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use diesel::result::DatabaseErrorKind;
 use diesel::result::Error as DieselError;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ErrorResponse {
     pub code: u16,
     pub description: String,
 }
 
-// (TODO): Add tracing to each of these for loggin
-
-/// Utility function for mapping any error into a `500 Internal Server Error`
-/// response.
-pub fn internal_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::fmt::Display,
-{
-    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+/// The unified error type for the API. Every variant renders as an
+/// [`ErrorResponse`] JSON body with the matching status code, so handlers
+/// can simply return `Result<Json<T>, AppError>` and use `?`.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound,
+    Conflict(String),
+    BadRequest(String),
+    Validation(validator::ValidationErrors),
+    Database(DieselError),
+    Internal(String),
+    Unauthorized(String),
+    Forbidden(String),
 }
 
-/// Utility function for database errors into appropriate responses
-pub fn database_error(err: diesel::result::Error) -> (StatusCode, String) {
-    match err {
-        DieselError::NotFound => not_found_error(err),
-        DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
-            unprocessable_entity_error(err)
-        }
-        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => conflict_error(err),
-        _ => internal_error(err),
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, description) = match self {
+            AppError::NotFound => (StatusCode::NOT_FOUND, "Not found".to_string()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Validation(errors) => (StatusCode::BAD_REQUEST, errors.to_string()),
+            AppError::Database(DieselError::DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            )) => (StatusCode::UNPROCESSABLE_ENTITY, "Invalid reference".to_string()),
+            AppError::Database(err) => {
+                tracing::error!(error = %err, "Database error");
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            }
+            AppError::Internal(msg) => {
+                tracing::error!(error = %msg, "Internal error");
+                (StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+        };
+
+        let body = ErrorResponse {
+            code: status.as_u16(),
+            description,
+        };
+
+        (status, Json(body)).into_response()
     }
 }
 
-/// Utility function for mapping any error into a `400 Bad Request`
-/// response and serializing it into a JSON response.
-pub fn bad_request_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::error::Error,
-{
-    let res = ErrorResponse {
-        code: StatusCode::BAD_REQUEST.as_u16(),
-        description: err.to_string(),
-    };
-
-    let res = serde_json::to_string(&res)
-        .unwrap_or_else(|_| "{\"code\":500,\"description\":\"Serialization error\"}".to_string());
-
-    (StatusCode::BAD_REQUEST, res)
-}
-
-/// Utility function for mapping any error into a `404 Not Found`
-/// response and serializing it into a JSON response.
-pub fn not_found_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::error::Error,
-{
-    let res = ErrorResponse {
-        code: StatusCode::NOT_FOUND.as_u16(),
-        description: err.to_string(),
-    };
-
-    let res = serde_json::to_string(&res)
-        .unwrap_or_else(|_| "{\"code\":500,\"description\":\"Serialization error\"}".to_string());
-
-    (StatusCode::NOT_FOUND, res)
-}
-
-/// Utility function for mapping any error into a `409 Unprocessable Entity`
-/// response and serializing it into a JSON response.
-pub fn conflict_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::error::Error,
-{
-    let res = ErrorResponse {
-        code: StatusCode::CONFLICT.as_u16(),
-        description: err.to_string(),
-    };
-
-    let res = serde_json::to_string(&res)
-        .unwrap_or_else(|_| "{\"code\":500,\"description\":\"Serialization error\"}".to_string());
-
-    (StatusCode::CONFLICT, res)
+impl From<DieselError> for AppError {
+    fn from(err: DieselError) -> Self {
+        match err {
+            DieselError::NotFound => AppError::NotFound,
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, ref info) => {
+                AppError::Conflict(info.message().to_string())
+            }
+            other => AppError::Database(other),
+        }
+    }
 }
 
-/// Utility function for mapping any error into a `422 Unprocessable Entity`
-/// response and serializing it into a JSON response.
-pub fn unprocessable_entity_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::error::Error,
-{
-    let res = ErrorResponse {
-        code: StatusCode::UNPROCESSABLE_ENTITY.as_u16(),
-        description: err.to_string(),
-    };
-
-    let res = serde_json::to_string(&res)
-        .unwrap_or_else(|_| "{\"code\":500,\"description\":\"Serialization error\"}".to_string());
-
-    (StatusCode::UNPROCESSABLE_ENTITY, res)
+impl From<validator::ValidationErrors> for AppError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        AppError::Validation(errors)
+    }
 }
@@ -1,5 +1,6 @@
 use std::env;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use axum::Router;
 use diesel_async::AsyncPgConnection;
@@ -8,12 +9,16 @@ use diesel_async::pooled_connection::deadpool::Pool;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::app::{AppState, build_api_router, build_public_router};
+use crate::auth::JwksCache;
+use crate::db::migrations::run_pending_migrations;
 
 mod api;
 mod app;
 mod auth;
 mod db;
 mod error;
+mod openapi;
+mod pagination;
 mod schema;
 
 const MAX_DB_CONNECTIONS: usize = 10;
@@ -59,7 +64,42 @@ async fn main() {
     }
     // ---- END DB ---
 
-    let app_state = AppState { db_pool: pool };
+    // `microaxum migrate` applies pending migrations and exits, giving
+    // operators a reproducible schema setup without an external diesel-cli
+    // step. Normal boot can also run migrations first via
+    // `RUN_MIGRATIONS_ON_BOOT=1`.
+    if env::args().nth(1).as_deref() == Some("migrate") {
+        run_pending_migrations(&pool)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to run migrations: {}", e);
+                panic!("Failed to run migrations: {}", e);
+            });
+        tracing::info!("Migrations applied successfully");
+        return;
+    }
+
+    if env::var("RUN_MIGRATIONS_ON_BOOT").is_ok_and(|v| v == "1" || v == "true") {
+        run_pending_migrations(&pool).await.unwrap_or_else(|e| {
+            tracing::error!("Failed to run migrations: {}", e);
+            panic!("Failed to run migrations: {}", e);
+        });
+    }
+
+    let clerk_issuer = std::env::var("CLERK_ISSUER").unwrap_or_else(|_| {
+        tracing::error!("CLERK_ISSUER environment variable not set");
+        panic!("CLERK_ISSUER must be set");
+    });
+    let authorized_party = std::env::var("CLERK_AUTHORIZED_PARTY").unwrap_or_else(|_| {
+        tracing::error!("CLERK_AUTHORIZED_PARTY environment variable not set");
+        panic!("CLERK_AUTHORIZED_PARTY must be set");
+    });
+
+    let app_state = AppState {
+        db_pool: pool,
+        jwks_cache: Arc::new(JwksCache::new(clerk_issuer)),
+        authorized_party,
+    };
 
     let public_router = build_public_router();
     let api_router = build_api_router(app_state);
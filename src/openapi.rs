@@ -0,0 +1,35 @@
+use utoipa::OpenApi;
+
+use crate::api::users::route as users;
+use crate::app::health_check;
+use crate::db::models::users::{NewUser, User, UserState, UserUpdate};
+use crate::error::ErrorResponse;
+use crate::pagination::Page;
+
+/// Aggregates the service's API surface into a single OpenAPI document,
+/// served at `/v1/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        health_check,
+        users::list_users,
+        users::get_user,
+        users::create_user,
+        users::update_user,
+        users::delete_user,
+        users::restore_user,
+        users::login,
+    ),
+    components(schemas(
+        User,
+        NewUser,
+        UserUpdate,
+        UserState,
+        ErrorResponse,
+        Page<User>,
+        users::LoginRequest,
+        users::LoginResponse,
+    )),
+    tags((name = "users", description = "User management")),
+)]
+pub struct ApiDoc;
@@ -0,0 +1,71 @@
+use std::sync::OnceLock;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A page of results from a keyset-paginated list endpoint.
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// Opaque cursor to pass as `?cursor=` to fetch the next page, or
+    /// `None` when this page came back with fewer rows than the limit. A
+    /// last page that is exactly full still carries a cursor, and the page
+    /// it leads to is empty.
+    pub next_cursor: Option<String>,
+}
+
+/// Server-held key used to authenticate cursors. Without this, a client
+/// could mint a cursor for any `(created_at, id)` pair it likes.
+fn cursor_secret() -> &'static [u8] {
+    static SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+    SECRET.get_or_init(|| {
+        std::env::var("PAGINATION_CURSOR_SECRET")
+            .expect("PAGINATION_CURSOR_SECRET must be set")
+            .into_bytes()
+    })
+}
+
+fn mac_for(payload: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(cursor_secret()).expect("HMAC accepts a key of any length");
+    mac.update(payload);
+    mac
+}
+
+/// Encodes a keyset position `(created_at, id)` into an opaque cursor. The
+/// cursor carries an HMAC tag computed with a server-held secret, so a
+/// client can't forge or tamper with one without knowing that secret.
+pub fn encode_cursor(created_at: i64, id: &str) -> String {
+    let payload = format!("{created_at}.{id}");
+    let tag = mac_for(payload.as_bytes()).finalize().into_bytes();
+
+    let payload = URL_SAFE_NO_PAD.encode(payload);
+    let tag = URL_SAFE_NO_PAD.encode(tag);
+    format!("{payload}.{tag}")
+}
+
+/// Decodes a cursor produced by [`encode_cursor`], rejecting anything that
+/// wasn't issued by this service (wrong or missing HMAC tag).
+pub fn decode_cursor(cursor: &str) -> Result<(i64, String), AppError> {
+    let invalid = || AppError::BadRequest("Invalid pagination cursor".to_string());
+
+    let (payload_b64, tag_b64) = cursor.split_once('.').ok_or_else(invalid)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload_b64).map_err(|_| invalid())?;
+    let tag = URL_SAFE_NO_PAD.decode(tag_b64).map_err(|_| invalid())?;
+
+    mac_for(&payload).verify_slice(&tag).map_err(|_| invalid())?;
+
+    let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+    let (created_at, id) = payload.split_once('.').ok_or_else(invalid)?;
+    let created_at = created_at.parse::<i64>().map_err(|_| invalid())?;
+
+    Ok((created_at, id.to_string()))
+}